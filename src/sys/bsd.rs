@@ -0,0 +1,349 @@
+use std::ffi::{CString, OsStr, OsString};
+use std::io;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::io::{AsRawFd, BorrowedFd};
+use std::path::Path;
+
+use crate::util::{allocate_loop, SetFlags};
+
+// FreeBSD/NetBSD have no notion of a single flat attribute namespace: every
+// name lives in either the user or the system namespace, and the namespace
+// is passed as a separate `attrnamespace` argument rather than being part of
+// the name itself. We accept/produce the same `user.foo` / `system.foo`
+// names the linux/macos backend uses and translate between the two forms
+// here.
+const EXTATTR_NAMESPACE_USER: libc::c_int = 1;
+const EXTATTR_NAMESPACE_SYSTEM: libc::c_int = 2;
+
+/// Splits a fully-qualified name like `user.foo` into the BSD namespace
+/// constant and the bare attribute name (`foo`).
+fn split_namespace(name: &OsStr) -> io::Result<(libc::c_int, CString)> {
+    let bytes = name.as_bytes();
+    let (namespace, rest) = if let Some(rest) = bytes.strip_prefix(b"user.") {
+        (EXTATTR_NAMESPACE_USER, rest)
+    } else if let Some(rest) = bytes.strip_prefix(b"system.") {
+        (EXTATTR_NAMESPACE_SYSTEM, rest)
+    } else {
+        return Err(io::Error::from_raw_os_error(libc::EINVAL));
+    };
+    let name = CString::new(rest).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    Ok((namespace, name))
+}
+
+/// Prepends the textual namespace prefix back onto a bare attribute name.
+fn qualify_namespace(namespace: Namespace, name: &[u8]) -> OsString {
+    let prefix: &[u8] = match namespace {
+        Namespace::User => b"user.",
+        Namespace::System => b"system.",
+    };
+    let mut qualified = Vec::with_capacity(prefix.len() + name.len());
+    qualified.extend_from_slice(prefix);
+    qualified.extend_from_slice(name);
+    OsString::from_vec(qualified)
+}
+
+#[derive(Clone, Copy)]
+enum Namespace {
+    User,
+    System,
+}
+
+/// An iterator over a set of extended attributes names.
+///
+/// `extattr_list_*` is per-namespace, so we fetch the user and system
+/// buffers up front and walk the user buffer first, then the system one.
+/// Each buffer is a sequence of entries of a single length byte followed by
+/// that many (non-NUL-terminated) name bytes.
+#[derive(Clone)]
+pub struct XAttrs {
+    user_attrs: Box<[u8]>,
+    system_attrs: Box<[u8]>,
+    namespace: Namespace,
+    offset: usize,
+}
+
+impl Iterator for XAttrs {
+    type Item = OsString;
+    fn next(&mut self) -> Option<OsString> {
+        loop {
+            let data = match self.namespace {
+                Namespace::User => &self.user_attrs,
+                Namespace::System => &self.system_attrs,
+            };
+            if self.offset == data.len() {
+                match self.namespace {
+                    Namespace::User => {
+                        self.namespace = Namespace::System;
+                        self.offset = 0;
+                    }
+                    Namespace::System => return None,
+                }
+                continue;
+            }
+
+            let len = data[self.offset] as usize;
+            let start = self.offset + 1;
+            let end = start + len;
+            self.offset = end;
+            return Some(qualify_namespace(self.namespace, &data[start..end]));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.namespace {
+            Namespace::User => (self.user_attrs.len() - self.offset) + self.system_attrs.len(),
+            Namespace::System => self.system_attrs.len() - self.offset,
+        };
+        if remaining == 0 {
+            (0, Some(0))
+        } else {
+            (1, None)
+        }
+    }
+}
+
+// `allocate_loop` first probes the closure with an empty buffer to learn
+// the value's size. Unlike Linux's getxattr, `extattr_get_*`/`extattr_list_*`
+// only report the real size when the `data` pointer is NULL — passing a
+// non-null dangling pointer with `nbytes == 0` (i.e. `buf.as_mut_ptr()` on
+// an empty slice) just copies zero bytes and reports a size of 0. Route
+// every call through this so an empty buffer always becomes a NULL/0 probe.
+fn extattr_buf_ptr(buf: &mut [u8]) -> (*mut libc::c_void, usize) {
+    if buf.is_empty() {
+        (std::ptr::null_mut(), 0)
+    } else {
+        (buf.as_mut_ptr() as *mut libc::c_void, buf.len())
+    }
+}
+
+fn extattr_result(ret: libc::ssize_t) -> io::Result<usize> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+fn list_fd_namespace(fd: BorrowedFd<'_>, namespace: libc::c_int) -> io::Result<Vec<u8>> {
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret = unsafe { libc::extattr_list_fd(fd.as_raw_fd(), namespace, data, nbytes) };
+        extattr_result(ret)
+    })
+}
+
+fn list_path_namespace(path: &Path, namespace: libc::c_int) -> io::Result<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret = unsafe { libc::extattr_list_link(path.as_ptr(), namespace, data, nbytes) };
+        extattr_result(ret)
+    })
+}
+
+// When listing the system namespace as an unprivileged user, the kernel
+// returns EPERM. Treat that the same as "no system attrs" so callers still
+// get the user attrs back instead of an outright failure.
+fn system_attrs_or_empty(result: io::Result<Vec<u8>>) -> io::Result<Vec<u8>> {
+    match result {
+        Ok(buf) => Ok(buf),
+        Err(e) if e.raw_os_error() == Some(libc::EPERM) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn get_fd(fd: BorrowedFd<'_>, name: &OsStr) -> io::Result<Vec<u8>> {
+    let (namespace, name) = split_namespace(name)?;
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret = unsafe {
+            libc::extattr_get_fd(fd.as_raw_fd(), namespace, name.as_ptr(), data, nbytes)
+        };
+        extattr_result(ret)
+    })
+}
+
+// FreeBSD/NetBSD's extattr_set_* syscalls have no equivalent of
+// XATTR_CREATE/XATTR_REPLACE: they always create-or-overwrite. There's no
+// way to ask the kernel for create-only/replace-only atomically here, so
+// anything other than `SetFlags::Any` is reported as unsupported rather
+// than emulated with a racy get-then-set.
+fn check_supported(flags: SetFlags) -> io::Result<()> {
+    match flags {
+        SetFlags::Any => Ok(()),
+        SetFlags::Create | SetFlags::Replace => {
+            Err(io::Error::from_raw_os_error(libc::EOPNOTSUPP))
+        }
+    }
+}
+
+pub fn set_fd(fd: BorrowedFd<'_>, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    check_supported(flags)?;
+    let (namespace, name) = split_namespace(name)?;
+    let ret = unsafe {
+        libc::extattr_set_fd(
+            fd.as_raw_fd(),
+            namespace,
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn remove_fd(fd: BorrowedFd<'_>, name: &OsStr) -> io::Result<()> {
+    let (namespace, name) = split_namespace(name)?;
+    let ret = unsafe { libc::extattr_delete_fd(fd.as_raw_fd(), namespace, name.as_ptr()) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn list_fd(fd: BorrowedFd<'_>) -> io::Result<XAttrs> {
+    let user_attrs = list_fd_namespace(fd, EXTATTR_NAMESPACE_USER)?;
+    let system_attrs = system_attrs_or_empty(list_fd_namespace(fd, EXTATTR_NAMESPACE_SYSTEM))?;
+    Ok(XAttrs {
+        user_attrs: user_attrs.into_boxed_slice(),
+        system_attrs: system_attrs.into_boxed_slice(),
+        namespace: Namespace::User,
+        offset: 0,
+    })
+}
+
+pub fn get_path(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret =
+            unsafe { libc::extattr_get_link(path.as_ptr(), namespace, name.as_ptr(), data, nbytes) };
+        extattr_result(ret)
+    })
+}
+
+pub fn set_path(path: &Path, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    check_supported(flags)?;
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    let ret = unsafe {
+        libc::extattr_set_link(
+            path.as_ptr(),
+            namespace,
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn remove_path(path: &Path, name: &OsStr) -> io::Result<()> {
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    let ret = unsafe { libc::extattr_delete_link(path.as_ptr(), namespace, name.as_ptr()) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn list_path(path: &Path) -> io::Result<XAttrs> {
+    let user_attrs = list_path_namespace(path, EXTATTR_NAMESPACE_USER)?;
+    let system_attrs =
+        system_attrs_or_empty(list_path_namespace(path, EXTATTR_NAMESPACE_SYSTEM))?;
+    Ok(XAttrs {
+        user_attrs: user_attrs.into_boxed_slice(),
+        system_attrs: system_attrs.into_boxed_slice(),
+        namespace: Namespace::User,
+        offset: 0,
+    })
+}
+
+// `_deref` variants of the path operations above: instead of operating on a
+// symlink itself, they follow it and operate on its target. These mirror
+// `get_path`/`set_path`/`remove_path`/`list_path` exactly, just swapping the
+// `extattr_*_link` (no-follow) syscalls for the `extattr_*_file` (follow)
+// ones.
+
+pub fn get_path_deref(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret =
+            unsafe { libc::extattr_get_file(path.as_ptr(), namespace, name.as_ptr(), data, nbytes) };
+        extattr_result(ret)
+    })
+}
+
+pub fn set_path_deref(path: &Path, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    check_supported(flags)?;
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    let ret = unsafe {
+        libc::extattr_set_file(
+            path.as_ptr(),
+            namespace,
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn remove_path_deref(path: &Path, name: &OsStr) -> io::Result<()> {
+    let (namespace, name) = split_namespace(name)?;
+    let path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    let ret = unsafe { libc::extattr_delete_file(path.as_ptr(), namespace, name.as_ptr()) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn list_path_deref(path: &Path) -> io::Result<XAttrs> {
+    let user_attrs = list_path_namespace_deref(path, EXTATTR_NAMESPACE_USER)?;
+    let system_attrs =
+        system_attrs_or_empty(list_path_namespace_deref(path, EXTATTR_NAMESPACE_SYSTEM))?;
+    Ok(XAttrs {
+        user_attrs: user_attrs.into_boxed_slice(),
+        system_attrs: system_attrs.into_boxed_slice(),
+        namespace: Namespace::User,
+        offset: 0,
+    })
+}
+
+fn list_path_namespace_deref(path: &Path, namespace: libc::c_int) -> io::Result<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    allocate_loop(|buf| {
+        let (data, nbytes) = extattr_buf_ptr(buf);
+        let ret = unsafe { libc::extattr_list_file(path.as_ptr(), namespace, data, nbytes) };
+        extattr_result(ret)
+    })
+}
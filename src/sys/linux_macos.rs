@@ -8,7 +8,7 @@ use std::path::Path;
 use rustix::fs as rfs;
 use rustix::path::Arg;
 
-use crate::util::allocate_loop;
+use crate::util::{allocate_loop, SetFlags};
 
 use std::os::raw::c_char;
 
@@ -69,14 +69,42 @@ impl Iterator for XAttrs {
     }
 }
 
+fn to_rustix(flags: SetFlags) -> rfs::XattrFlags {
+    match flags {
+        SetFlags::Any => rfs::XattrFlags::empty(),
+        SetFlags::Create => rfs::XattrFlags::CREATE,
+        SetFlags::Replace => rfs::XattrFlags::REPLACE,
+    }
+}
+
+// If an empty slice is passed to getxattr on macOS, it returns an error.
+// Might be a macOS bug, so work around it here by calling the libc manually
+// with a null buffer to just probe the size. Shared by every macOS path
+// that falls back to rustix (or libc directly, for the `_at` variants) once
+// it knows the real buffer size.
+#[cfg(target_os = "macos")]
+fn macos_getxattr_probe(
+    path: *const c_char,
+    name: *const c_char,
+    position: u32,
+    flags: libc::c_int,
+) -> io::Result<usize> {
+    let ret = unsafe { libc::getxattr(path, name, std::ptr::null_mut(), 0, position, flags) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
 pub fn get_fd(fd: BorrowedFd<'_>, name: &OsStr) -> io::Result<Vec<u8>> {
     allocate_loop(|buf| {
         rfs::fgetxattr(fd, name, buf)
     })
 }
 
-pub fn set_fd(fd: BorrowedFd<'_>, name: &OsStr, value: &[u8]) -> io::Result<()> {
-    rfs::fsetxattr(fd, name, value, rfs::XattrFlags::empty())?;
+pub fn set_fd(fd: BorrowedFd<'_>, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    rfs::fsetxattr(fd, name, value, to_rustix(flags))?;
     Ok(())
 }
 
@@ -102,25 +130,13 @@ pub fn get_path(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
     allocate_loop(|buf| {
         #[cfg(target_os = "macos")]
         {
-            // If an empty slice is passed to lgetxattr on macOS, it returns an error.
-            // Might be a macOS bug, so work around it here by calling the libc manually.
             if buf.is_empty() {
-                let ret = unsafe {
-                    libc::getxattr(
-                        (&*path).as_ptr(),
-                        (&*name).as_ptr(),
-                        std::ptr::null_mut(),
-                        0,
-                        0,
-                        libc::XATTR_NOFOLLOW
-                    )
-                };
-
-                if ret < 0 {
-                    return Err(io::Error::last_os_error());
-                } else {
-                    return Ok(ret as usize);
-                }
+                return macos_getxattr_probe(
+                    (&*path).as_ptr(),
+                    (&*name).as_ptr(),
+                    0,
+                    libc::XATTR_NOFOLLOW,
+                );
             }
         }
 
@@ -129,8 +145,8 @@ pub fn get_path(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
     })
 }
 
-pub fn set_path(path: &Path, name: &OsStr, value: &[u8]) -> io::Result<()> {
-    rfs::lsetxattr(path, name, value, rfs::XattrFlags::empty())?;
+pub fn set_path(path: &Path, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    rfs::lsetxattr(path, name, value, to_rustix(flags))?;
     Ok(())
 }
 
@@ -149,3 +165,121 @@ pub fn list_path(path: &Path) -> io::Result<XAttrs> {
         offset: 0,
     })
 }
+
+// `_deref` variants of the path operations above: instead of operating on a
+// symlink itself, they follow it and operate on its target. These mirror
+// `get_path`/`set_path`/`remove_path`/`list_path` exactly, just swapping the
+// `l*` (no-follow) rustix calls for their following counterparts, and on
+// macOS dropping `XATTR_NOFOLLOW` from the manual empty-buffer workaround.
+
+pub fn get_path_deref(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+    let path = path.into_c_str()?;
+    let name = name.into_c_str()?;
+
+    allocate_loop(|buf| {
+        #[cfg(target_os = "macos")]
+        {
+            if buf.is_empty() {
+                return macos_getxattr_probe((&*path).as_ptr(), (&*name).as_ptr(), 0, 0);
+            }
+        }
+
+        let size = rfs::getxattr(&*path, &*name, buf)?;
+        io::Result::Ok(size)
+    })
+}
+
+pub fn set_path_deref(path: &Path, name: &OsStr, value: &[u8], flags: SetFlags) -> io::Result<()> {
+    rfs::setxattr(path, name, value, to_rustix(flags))?;
+    Ok(())
+}
+
+pub fn remove_path_deref(path: &Path, name: &OsStr) -> io::Result<()> {
+    rfs::removexattr(path, name)?;
+    Ok(())
+}
+
+pub fn list_path_deref(path: &Path) -> io::Result<XAttrs> {
+    let path = path.as_cow_c_str()?;
+    let vec = allocate_loop(|buf| {
+        rfs::listxattr(&*path, as_listxattr_buffer(buf))
+    })?;
+    Ok(XAttrs {
+        data: vec.into_boxed_slice(),
+        offset: 0,
+    })
+}
+
+// macOS's getxattr/setxattr take a trailing `position` argument, meaningful
+// only for sectioned attributes like `com.apple.ResourceFork`, letting
+// callers read/write a slice of a large attribute instead of the whole
+// value at once. rustix doesn't expose it, so these go through libc
+// directly, the same way the macOS empty-buffer workaround in `get_path`
+// does. There's no such concept on Linux, so it's unsupported there.
+
+#[cfg(target_os = "macos")]
+pub fn get_path_at(path: &Path, name: &OsStr, position: u32) -> io::Result<Vec<u8>> {
+    let path = path.into_c_str()?;
+    let name = name.into_c_str()?;
+
+    allocate_loop(|buf| {
+        if buf.is_empty() {
+            return macos_getxattr_probe(
+                (&*path).as_ptr(),
+                (&*name).as_ptr(),
+                position,
+                libc::XATTR_NOFOLLOW,
+            );
+        }
+
+        let ret = unsafe {
+            libc::getxattr(
+                (&*path).as_ptr(),
+                (&*name).as_ptr(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                position,
+                libc::XATTR_NOFOLLOW,
+            )
+        };
+
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(ret as usize)
+        }
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn get_path_at(_path: &Path, _name: &OsStr, _position: u32) -> io::Result<Vec<u8>> {
+    Err(io::Error::from_raw_os_error(libc::ENOTSUP))
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_path_at(path: &Path, name: &OsStr, value: &[u8], position: u32) -> io::Result<()> {
+    let path = path.into_c_str()?;
+    let name = name.into_c_str()?;
+
+    let ret = unsafe {
+        libc::setxattr(
+            (&*path).as_ptr(),
+            (&*name).as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            position,
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_path_at(_path: &Path, _name: &OsStr, _value: &[u8], _position: u32) -> io::Result<()> {
+    Err(io::Error::from_raw_os_error(libc::ENOTSUP))
+}
@@ -0,0 +1,142 @@
+use std::fmt::Write as _;
+
+/// Classification of a raw attribute value as UTF-8 text or arbitrary
+/// binary data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Text,
+    Binary,
+}
+
+/// A view over a raw attribute value (as returned by `get_fd`/`get_path`)
+/// that classifies it as text vs. binary and produces canonical, truncated
+/// renderings for display.
+///
+/// Many attribute values (e.g. `com.apple.*` plist/FinderInfo blobs) are
+/// non-UTF-8 binary data. This mirrors how file-listing tools decide
+/// whether to print an xattr value directly or fall back to a truncated
+/// hex/base64 form, keeping that logic in one place instead of every
+/// downstream crate reinventing it.
+pub struct XAttrValue<'a>(&'a [u8]);
+
+impl<'a> XAttrValue<'a> {
+    pub fn new(value: &'a [u8]) -> Self {
+        XAttrValue(value)
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        match std::str::from_utf8(self.0) {
+            Ok(_) => ValueKind::Text,
+            Err(_) => ValueKind::Binary,
+        }
+    }
+
+    /// The value as UTF-8 text, if it is valid UTF-8.
+    pub fn as_text(&self) -> Option<&'a str> {
+        std::str::from_utf8(self.0).ok()
+    }
+
+    /// A lossless hex dump of the value, considering at most `max_len`
+    /// input bytes. An ellipsis is appended when the value is longer than
+    /// `max_len`.
+    pub fn to_hex(&self, max_len: usize) -> String {
+        let (bytes, truncated) = truncate(self.0, max_len);
+        let mut out = String::with_capacity(bytes.len() * 2 + 3);
+        for byte in bytes {
+            write!(out, "{:02x}", byte).unwrap();
+        }
+        if truncated {
+            out.push_str("...");
+        }
+        out
+    }
+
+    /// A base64 encoding of the value, considering at most `max_len` input
+    /// bytes. An ellipsis is appended when the value is longer than
+    /// `max_len`.
+    pub fn to_base64(&self, max_len: usize) -> String {
+        let (bytes, truncated) = truncate(self.0, max_len);
+        let mut out = encode_base64(bytes);
+        if truncated {
+            out.push_str("...");
+        }
+        out
+    }
+}
+
+fn truncate(value: &[u8], max_len: usize) -> (&[u8], bool) {
+    if value.len() > max_len {
+        (&value[..max_len], true)
+    } else {
+        (value, false)
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = *chunk.get(1).unwrap_or(&0) as u32;
+        let b2 = *chunk.get(2).unwrap_or(&0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(BASE64_ALPHABET[(n >> 18 & 0x3f) as usize] as char);
+        out.push(BASE64_ALPHABET[(n >> 12 & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(n >> 6 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(n & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_classifies_text_vs_binary() {
+        assert_eq!(XAttrValue::new(b"hello").kind(), ValueKind::Text);
+        assert_eq!(XAttrValue::new(b"\xff\xfe").kind(), ValueKind::Binary);
+        assert_eq!(XAttrValue::new(b"").kind(), ValueKind::Text);
+    }
+
+    #[test]
+    fn as_text_round_trips_utf8() {
+        assert_eq!(XAttrValue::new(b"hello").as_text(), Some("hello"));
+        assert_eq!(XAttrValue::new(b"\xff\xfe").as_text(), None);
+    }
+
+    #[test]
+    fn to_hex_is_lossless_and_truncates() {
+        assert_eq!(XAttrValue::new(b"\x00\x01\xff").to_hex(16), "0001ff");
+        assert_eq!(XAttrValue::new(b"\x00\x01\xff").to_hex(2), "0001...");
+        assert_eq!(XAttrValue::new(b"\x00\x01\xff").to_hex(3), "0001ff");
+    }
+
+    #[test]
+    fn to_base64_matches_known_vectors() {
+        // RFC 4648 test vectors, covering every tail-length/padding case.
+        assert_eq!(XAttrValue::new(b"").to_base64(100), "");
+        assert_eq!(XAttrValue::new(b"f").to_base64(100), "Zg==");
+        assert_eq!(XAttrValue::new(b"fo").to_base64(100), "Zm8=");
+        assert_eq!(XAttrValue::new(b"foo").to_base64(100), "Zm9v");
+        assert_eq!(XAttrValue::new(b"foob").to_base64(100), "Zm9vYg==");
+        assert_eq!(XAttrValue::new(b"fooba").to_base64(100), "Zm9vYmE=");
+        assert_eq!(XAttrValue::new(b"foobar").to_base64(100), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn to_base64_truncates_before_encoding() {
+        assert_eq!(XAttrValue::new(b"foobar").to_base64(3), "Zm9v...");
+    }
+}